@@ -0,0 +1,1887 @@
+// `ASTNode` is already a recursive, boxed tree (`Box<ASTNode>` in every
+// single-child field), so a function/block body's `Vec<Box<ASTNode>>`
+// matches the indirection the rest of the node already uses rather than
+// mixing `Vec<ASTNode>` in for just the multi-child fields.
+#![allow(clippy::vec_box)]
+
+use std::process::exit;
+
+pub mod libc {
+    pub struct LibC {
+        pub filepaths: Vec<String>,
+        pub stdio: Stdio
+    }
+
+    impl LibC {
+        pub fn new() -> Self {
+            let filepaths: Vec<String> = vec![
+                "stdio.h".to_string()
+            ];
+            let stdio = Stdio::new();
+
+            Self { filepaths, stdio }
+        }
+    }
+
+    pub struct Stdio {
+        pub funcnames: Vec<String>
+    }
+
+    impl Stdio {
+        pub fn new() -> Self {
+            let funcnames: Vec<String> = vec![
+                "printf".to_string()
+            ];
+
+            Self { funcnames }
+        }
+
+        pub fn printf(&mut self, x: &str) {
+            print!("{x}");
+        }
+    }
+}
+
+pub mod runtime {
+    use ast::{AST, ASTNode};
+    use diag;
+    use libc::LibC;
+    use std::collections::HashMap;
+    use types::{FuncParam, Span, TokenKind};
+
+    #[derive(Debug)]
+    struct Function {
+        name: String,
+        location: String
+    }
+
+    /// A user-defined `FuncDecl`, kept around so calls can bind its
+    /// parameters and run its body.
+    #[derive(Clone, Debug)]
+    struct UserFunc {
+        params: Vec<FuncParam>,
+        body: Vec<Box<ASTNode>>,
+    }
+
+    #[derive(Debug)]
+    struct Env {
+        functions: Vec<Function>,
+        user_funcs: HashMap<String, UserFunc>,
+        /// A stack of variable frames: one pushed per function call, with
+        /// the innermost (last) frame searched first so locals shadow
+        /// whatever an enclosing frame defines.
+        frames: Vec<HashMap<String, i32>>,
+    }
+
+    impl Env {
+        pub fn new() -> Self {
+            Self {
+                functions: vec![],
+                user_funcs: HashMap::new(),
+                frames: vec![HashMap::new()],
+            }
+        }
+
+        pub fn push_function(&mut self, function: Function) {
+            self.functions.push(function);
+        }
+
+        pub fn find_libc_func(&self, name: &str) -> Option<&Function> {
+            self.functions.iter().find(|f| f.name == name)
+        }
+
+        pub fn define_func(&mut self, name: String, params: Vec<FuncParam>, body: Vec<Box<ASTNode>>) {
+            self.user_funcs.insert(name, UserFunc { params, body });
+        }
+
+        pub fn find_user_func(&self, name: &str) -> Option<&UserFunc> {
+            self.user_funcs.get(name)
+        }
+
+        pub fn push_frame(&mut self) {
+            self.frames.push(HashMap::new());
+        }
+
+        pub fn pop_frame(&mut self) {
+            self.frames.pop();
+        }
+
+        pub fn define(&mut self, name: String, value: i32) {
+            self.frames.last_mut().unwrap().insert(name, value);
+        }
+
+        pub fn get(&self, name: &str) -> Option<i32> {
+            self.frames.iter().rev().find_map(|frame| frame.get(name).copied())
+        }
+    }
+
+    pub struct Interpreter<'a> {
+        src: &'a [char],
+        ast: AST,
+        env: Env,
+        libc: LibC,
+        /// Decremented on every `while` iteration; bounds how long a loop
+        /// can run the way `vm::VmLimits::fuel` bounds the `Vm`, since the
+        /// grammar has no assignment a condition could ever use to exit on
+        /// its own.
+        fuel: usize,
+    }
+
+    impl<'a> Interpreter<'a> {
+        pub fn new(src: &'a [char], ast: AST) -> Self {
+            Self {
+                src,
+                ast,
+                env: Env::new(),
+                libc: LibC::new(),
+                fuel: 1_000_000,
+            }
+        }
+
+        pub fn eval(&mut self) {
+            loop {
+                let node = match self.ast.next() {
+                    Some(ASTNode::EOF) | None => break,
+                    Some(node) => node,
+                };
+
+                if let Some(value) = self.eval_node_stmt(node) {
+                    println!("{value}");
+                }
+            }
+
+            if self.env.find_user_func("main").is_some() {
+                if let Some(value) = self.call_function("main", vec![], Span::new(0, 0)) {
+                    println!("{value}");
+                }
+            }
+        }
+
+        /// Executes one statement. Returns `Some(value)` when it (or a
+        /// nested block it ran) hit a `return`, so callers can stop running
+        /// the rest of their own block and propagate the value upward.
+        fn eval_node_stmt(&mut self, node: ASTNode) -> Option<i32> {
+            match node {
+                ASTNode::Include(filepath, span) => {
+                    self.eval_node_include(filepath, span);
+                    None
+                }
+                ASTNode::FuncDecl { name, params, body, .. } => {
+                    self.env.define_func(name, params, body);
+                    None
+                }
+                ASTNode::VarDecl { name, init, .. } => {
+                    let value = self.eval_expr(*init);
+                    self.env.define(name, value);
+                    None
+                }
+                ASTNode::If { cond, then, else_, .. } => {
+                    if self.eval_expr(*cond) != 0 {
+                        self.eval_block(&then)
+                    } else if let Some(else_) = else_ {
+                        self.eval_block(&else_)
+                    } else {
+                        None
+                    }
+                }
+                ASTNode::While { cond, body, span } => {
+                    while self.eval_expr((*cond).clone()) != 0 {
+                        if self.fuel == 0 {
+                            diag::report(self.src, span, "interpreter ran out of fuel (possible infinite loop)");
+                        }
+                        self.fuel -= 1;
+
+                        if let Some(value) = self.eval_block(&body) {
+                            return Some(value);
+                        }
+                    }
+                    None
+                }
+                ASTNode::Return(expr, _span) => Some(self.eval_expr(*expr)),
+                ASTNode::FunCall { name, args, span } => {
+                    self.eval_func_call(name, args, span);
+                    None
+                }
+                ASTNode::Semicolon(_) => None,
+                _ => {
+                    let span = node.span();
+                    diag::report(
+                        self.src,
+                        span,
+                        &format!("evaluation of {:#?} not supported yet", node),
+                    );
+                }
+            }
+        }
+
+        /// Runs a `{ ... }` block's statements in order, stopping early (and
+        /// returning its value) the moment one of them returns.
+        fn eval_block(&mut self, body: &[Box<ASTNode>]) -> Option<i32> {
+            for stmt in body {
+                if let Some(value) = self.eval_node_stmt((**stmt).clone()) {
+                    return Some(value);
+                }
+            }
+            None
+        }
+
+        fn eval_node_include(&mut self, filepath: String, span: Span) {
+            if !self.libc.filepaths.contains(&filepath) {
+                diag::report(
+                    self.src,
+                    span,
+                    &format!("file {filepath} not found; only looking for libc files for now"),
+                );
+            }
+
+            for func in &self.libc.stdio.funcnames {
+                self.env.push_function(Function {
+                    name: func.to_string(),
+                    location: String::from(format!("{}/{}", "libc", func)),
+                })
+            }
+        }
+
+        /// Calls a user-defined function: evaluates `args` in the caller's
+        /// scope, binds them to the callee's parameters in a fresh frame,
+        /// then runs its body.
+        fn call_function(&mut self, name: &str, args: Vec<Box<ASTNode>>, span: Span) -> Option<i32> {
+            let user_func = match self.env.find_user_func(name) {
+                Some(user_func) => user_func.clone(),
+                None => diag::report(self.src, span, &format!("call to undefined function `{name}`")),
+            };
+
+            let evaluated: Vec<i32> = args.into_iter().map(|arg| self.eval_expr(*arg)).collect();
+
+            if evaluated.len() != user_func.params.len() {
+                diag::report(
+                    self.src,
+                    span,
+                    &format!(
+                        "`{name}` expects {} argument(s) but got {}",
+                        user_func.params.len(),
+                        evaluated.len()
+                    ),
+                );
+            }
+
+            self.env.push_frame();
+            for (param, value) in user_func.params.iter().zip(evaluated) {
+                self.env.define(param.name.clone(), value);
+            }
+
+            let result = self.eval_block(&user_func.body);
+            self.env.pop_frame();
+
+            result
+        }
+
+        fn eval_func_call(&mut self, name: String, args: Vec<Box<ASTNode>>, span: Span) -> i32 {
+            if self.env.find_libc_func(&name).is_some() {
+                return self.eval_libc_call(&name, args, span);
+            }
+
+            self.call_function(&name, args, span).unwrap_or(0)
+        }
+
+        fn eval_libc_call(&mut self, name: &str, args: Vec<Box<ASTNode>>, span: Span) -> i32 {
+            match name {
+                "printf" => self.eval_builtin_printf(args, span),
+                _ => diag::report(self.src, span, &format!("libc function `{name}` not supported yet")),
+            }
+        }
+
+        fn eval_builtin_printf(&mut self, args: Vec<Box<ASTNode>>, span: Span) -> i32 {
+            let arg = args
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| diag::report(self.src, span, "printf needs a string argument"));
+
+            match *arg {
+                ASTNode::StrVal(text, _) | ASTNode::StrLit(text, _) => {
+                    self.libc.stdio.printf(&text);
+                    0
+                }
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    "printf only supports a literal string argument",
+                ),
+            }
+        }
+
+        fn eval_expr(&mut self, node: ASTNode) -> i32 {
+            match node {
+                ASTNode::IntLit(value, _) => value,
+                ASTNode::StrLit(name, span) => self
+                    .env
+                    .get(&name)
+                    .unwrap_or_else(|| diag::report(self.src, span, &format!("undefined variable `{name}`"))),
+                ASTNode::FunCall { name, args, span } => self.eval_func_call(name, args, span),
+                ASTNode::BinaryOp { op, lhs, rhs, span } => {
+                    let lhs = self.eval_expr(*lhs);
+                    let rhs = self.eval_expr(*rhs);
+                    match op {
+                        TokenKind::PluSymb => self.checked_arith(lhs.checked_add(rhs), "addition", span),
+                        TokenKind::MinSymb => self.checked_arith(lhs.checked_sub(rhs), "subtraction", span),
+                        TokenKind::MulSymb => self.checked_arith(lhs.checked_mul(rhs), "multiplication", span),
+                        TokenKind::DivSymb => {
+                            if rhs == 0 {
+                                diag::report(self.src, span, "division by zero");
+                            }
+                            self.checked_arith(lhs.checked_div(rhs), "division", span)
+                        }
+                        _ => diag::report(
+                            self.src,
+                            span,
+                            &format!("unsupported binary operator {:?}", op),
+                        ),
+                    }
+                }
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("expected an integer expression but found {:#?}", other),
+                ),
+            }
+        }
+
+        /// Aborts with a clean diagnostic instead of panicking on a Rust
+        /// integer overflow when `op_name`'s checked arithmetic overflows.
+        fn checked_arith(&self, result: Option<i32>, op_name: &str, span: Span) -> i32 {
+            result.unwrap_or_else(|| diag::report(self.src, span, &format!("integer overflow in {op_name}")))
+        }
+
+    }
+}
+
+pub mod types {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+    }
+
+    impl Span {
+        pub fn new(start: usize, end: usize) -> Self {
+            Self { start, end }
+        }
+
+        /// Builds a span that covers `self` through `other`, for nodes made
+        /// up of several tokens (e.g. a `FuncDecl` spans from its return
+        /// type to its closing `}`).
+        pub fn to(&self, other: Span) -> Span {
+            Span::new(self.start, other.end)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Type {
+        INT,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct FuncParam {
+        pub ttype: Type,
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Token {
+        pub kind: TokenKind,
+        pub value: String,
+        pub span: Span,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TokenKind {
+        StrLit,
+        StrVal,
+        Numeric,
+        PluSymb,
+        MinSymb,
+        MulSymb,
+        DivSymb,
+        OpenPar,
+        ClosPar,
+        OpenBlk,
+        ClosBlk,
+        Colon,
+        Comma,
+        Equal,
+        Semicolon,
+        Dot,
+        Hash,
+        LessThan,
+        GraThan,
+    }
+
+    impl TokenKind {
+        /// The user-facing spelling of this kind, for diagnostics — C
+        /// punctuation for symbols, a plain-English name for the rest, so
+        /// `diag::report` never leaks a `TokenKind` debug name to `main.c`'s
+        /// author.
+        pub fn describe(&self) -> &'static str {
+            match self {
+                TokenKind::StrLit => "identifier",
+                TokenKind::StrVal => "string literal",
+                TokenKind::Numeric => "number",
+                TokenKind::PluSymb => "'+'",
+                TokenKind::MinSymb => "'-'",
+                TokenKind::MulSymb => "'*'",
+                TokenKind::DivSymb => "'/'",
+                TokenKind::OpenPar => "'('",
+                TokenKind::ClosPar => "')'",
+                TokenKind::OpenBlk => "'{'",
+                TokenKind::ClosBlk => "'}'",
+                TokenKind::Colon => "':'",
+                TokenKind::Comma => "','",
+                TokenKind::Equal => "'='",
+                TokenKind::Semicolon => "';'",
+                TokenKind::Dot => "'.'",
+                TokenKind::Hash => "'#'",
+                TokenKind::LessThan => "'<'",
+                TokenKind::GraThan => "'>'",
+            }
+        }
+    }
+}
+
+pub mod ast {
+    use types::{FuncParam, Span, TokenKind, Type};
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum ASTNode {
+        Include(String, Span),
+        FuncDecl {
+            name: String,
+            params: Vec<FuncParam>,
+            ret_type: Type,
+            body: Vec<Box<ASTNode>>,
+            span: Span,
+        },
+        FunCall {
+            name: String,
+            args: Vec<Box<ASTNode>>,
+            span: Span,
+        },
+        BinaryOp {
+            op: TokenKind,
+            lhs: Box<ASTNode>,
+            rhs: Box<ASTNode>,
+            span: Span,
+        },
+        VarDecl {
+            ttype: Type,
+            name: String,
+            init: Box<ASTNode>,
+            span: Span,
+        },
+        If {
+            cond: Box<ASTNode>,
+            then: Vec<Box<ASTNode>>,
+            else_: Option<Vec<Box<ASTNode>>>,
+            span: Span,
+        },
+        While {
+            cond: Box<ASTNode>,
+            body: Vec<Box<ASTNode>>,
+            span: Span,
+        },
+        Return(Box<ASTNode>, Span),
+        StrLit(String, Span),
+        StrVal(String, Span),
+        IntLit(i32, Span),
+        Semicolon(Span),
+        EOF,
+    }
+
+    impl ASTNode {
+        /// The source span this node was parsed from, used to point
+        /// diagnostics at the right place in `main.c`.
+        pub fn span(&self) -> Span {
+            match self {
+                ASTNode::Include(_, span) => *span,
+                ASTNode::FuncDecl { span, .. } => *span,
+                ASTNode::FunCall { span, .. } => *span,
+                ASTNode::BinaryOp { span, .. } => *span,
+                ASTNode::VarDecl { span, .. } => *span,
+                ASTNode::If { span, .. } => *span,
+                ASTNode::While { span, .. } => *span,
+                ASTNode::Return(_, span) => *span,
+                ASTNode::StrLit(_, span) => *span,
+                ASTNode::StrVal(_, span) => *span,
+                ASTNode::IntLit(_, span) => *span,
+                ASTNode::Semicolon(span) => *span,
+                ASTNode::EOF => Span::new(0, 0),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct AST {
+        body: Vec<ASTNode>,
+    }
+
+    impl AST {
+        pub fn new() -> Self {
+            Self { body: Vec::new() }
+        }
+
+        pub fn push(&mut self, token: ASTNode) {
+            self.body.push(token);
+        }
+
+        pub fn dump(&mut self) {
+            println!("{:#?}", self);
+        }
+
+        pub fn next(&mut self) -> Option<ASTNode> {
+            if self.body.is_empty() {
+                return None;
+            }
+            Some(self.body.remove(0))
+        }
+    }
+}
+
+pub mod diag {
+    use std::process::exit;
+    use types::Span;
+
+    /// Computes the 1-indexed `(line, column)` of a char offset into `src`.
+    pub fn line_col(src: &[char], offset: usize) -> (usize, usize) {
+        let mut line = 1;
+        let mut col = 1;
+
+        for &c in &src[..offset.min(src.len())] {
+            if c == '\n' {
+                line += 1;
+                col = 1;
+            } else {
+                col += 1;
+            }
+        }
+
+        (line, col)
+    }
+
+    fn line_text(src: &[char], line: usize) -> String {
+        src.iter()
+            .collect::<String>()
+            .lines()
+            .nth(line - 1)
+            .unwrap_or("")
+            .to_string()
+    }
+
+    /// Prints a `main.c:line:col: error: <message>` diagnostic with the
+    /// offending source line and a caret under the start of `span`, then exits
+    /// the process. This is how every parse/eval error should be reported
+    /// instead of pointing at the interpreter's own `file!()`/`line!()`.
+    pub fn report(src: &[char], span: Span, message: &str) -> ! {
+        let (line, col) = line_col(src, span.start);
+
+        eprintln!("main.c:{}:{}: error: {}", line, col, message);
+        eprintln!("{}", line_text(src, line));
+        eprintln!("{}^", " ".repeat(col.saturating_sub(1)));
+
+        exit(1);
+    }
+}
+
+pub mod parser {
+    use ast::{ASTNode, AST};
+    use diag;
+    use types::{FuncParam, Span, Token, TokenKind as TK, Type};
+
+    pub struct Parser<'a> {
+        src: &'a [char],
+        tokens: Vec<Token>,
+        ast: AST,
+    }
+
+    impl<'a> Parser<'a> {
+        pub fn new(src: &'a [char], tokens: Vec<Token>) -> Self {
+            Self {
+                src,
+                tokens,
+                ast: AST::new(),
+            }
+        }
+
+        pub fn parse(&mut self) -> AST {
+            while !self.eof() {
+                let node = self.parse_stmt();
+                self.ast.push(node);
+            }
+
+            self.ast.push(ASTNode::EOF);
+            self.ast.clone()
+        }
+
+        fn parse_stmt(&mut self) -> ASTNode {
+            let at = self.at();
+
+            /* Handles:
+             *   # ...
+             */
+            if at.kind == TK::Hash {
+                self.eat(); // remove `#`
+                return self.parse_deretive(at.span);
+            }
+
+            /* Handles:
+             *   int ...
+             */
+            if at.kind == TK::StrLit && self.is_decl(&at.value) {
+                self.eat(); // remove `int` or ...
+                return self.parse_decl(at);
+            }
+
+            if at.value.as_str() == "return" {
+                self.eat(); // remove `return`
+                return self.parse_return(at.span);
+            }
+
+            if at.value.as_str() == "if" {
+                self.eat(); // remove `if`
+                return self.parse_if(at.span);
+            }
+
+            if at.value.as_str() == "while" {
+                self.eat(); // remove `while`
+                return self.parse_while(at.span);
+            }
+
+            self.parse_expr()
+        }
+
+        /* Handles:
+         *   if (cond) { ... } else { ... }
+         */
+        fn parse_if(&mut self, start: Span) -> ASTNode {
+            self.eat_kind(TK::OpenPar);
+            let cond = self.parse_expr();
+            self.eat_kind(TK::ClosPar);
+
+            let (then, mut end) = self.parse_block();
+
+            let else_ = if self.at().value.as_str() == "else" {
+                self.eat(); // remove `else`
+                let (body, else_end) = self.parse_block();
+                end = else_end;
+                Some(body)
+            } else {
+                None
+            };
+
+            ASTNode::If {
+                cond: Box::new(cond),
+                then,
+                else_,
+                span: start.to(end),
+            }
+        }
+
+        /* Handles:
+         *   while (cond) { ... }
+         */
+        fn parse_while(&mut self, start: Span) -> ASTNode {
+            self.eat_kind(TK::OpenPar);
+            let cond = self.parse_expr();
+            self.eat_kind(TK::ClosPar);
+
+            let (body, end) = self.parse_block();
+
+            ASTNode::While {
+                cond: Box::new(cond),
+                body,
+                span: start.to(end),
+            }
+        }
+
+        /// Parses a brace-delimited `{ ... }` block of statements, returning
+        /// the statements and the span of the whole block (including the
+        /// braces themselves).
+        fn parse_block(&mut self) -> (Vec<Box<ASTNode>>, Span) {
+            let open = self.eat_kind(TK::OpenBlk);
+
+            let mut body: Vec<Box<ASTNode>> = vec![];
+            while self.at().kind != TK::ClosBlk {
+                body.push(Box::new(self.parse_stmt()));
+            }
+            let close = self.eat_kind(TK::ClosBlk);
+
+            (body, open.span.to(close.span))
+        }
+
+        fn parse_expr(&mut self) -> ASTNode {
+            self.parse_expr_bp(0)
+        }
+
+        /// Precedence-climbing (Pratt) parse of a binary expression: parses
+        /// a primary/func-call as the left operand, then keeps eating binary
+        /// operators whose left binding power is at least `min_bp`, parsing
+        /// the right side with that operator's right binding power. Giving
+        /// `+ - * /` a right binding power one greater than their left makes
+        /// them left-associative.
+        fn parse_expr_bp(&mut self, min_bp: u8) -> ASTNode {
+            let mut lhs = self.parse_func_call();
+
+            loop {
+                let (l_bp, r_bp) = match binding_power(&self.at().kind) {
+                    Some(bp) => bp,
+                    None => break,
+                };
+                if l_bp < min_bp {
+                    break;
+                }
+
+                let op = self.eat().kind; // remove the operator
+                let rhs = self.parse_expr_bp(r_bp);
+                let span = lhs.span().to(rhs.span());
+
+                lhs = ASTNode::BinaryOp {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                };
+            }
+
+            lhs
+        }
+
+        fn parse_func_call(&mut self) -> ASTNode {
+            let func = self.parse_prim_expr();
+            if self.at().kind == TK::OpenPar {
+                self.eat(); // remove `(`
+
+                let mut args: Vec<Box<ASTNode>> = vec![];
+                while self.at().kind != TK::ClosPar {
+                    args.push(Box::new(self.parse_expr()));
+                    if self.at().kind == TK::Comma {
+                        self.eat(); // remove `,`
+                    }
+                }
+                let clos = self.eat_kind(TK::ClosPar); // remove `)`
+
+                return ASTNode::FunCall {
+                    span: func.span().to(clos.span),
+                    name: self.get_strlit_val(&func),
+                    args,
+                };
+            }
+            func
+        }
+
+        fn parse_return(&mut self, start: Span) -> ASTNode {
+            let expr = self.parse_expr();
+            let span = start.to(expr.span());
+            ASTNode::Return(Box::new(expr), span)
+        }
+
+        fn parse_decl(&mut self, prev_tok: Token) -> ASTNode {
+            let at = self.eat_kind(TK::StrLit);
+
+            /* TODO:
+             *   - Validate at.value as identifier
+             */
+
+            match self.eat().kind {
+                TK::OpenPar => self.parse_decl_func(
+                    self.str2type(&prev_tok.value, prev_tok.span),
+                    at.value,
+                    prev_tok.span,
+                ),
+                TK::Equal => self.parse_var_decl(
+                    self.str2type(&prev_tok.value, prev_tok.span),
+                    at.value,
+                    prev_tok.span,
+                ),
+                _ => diag::report(
+                    self.src,
+                    at.span,
+                    "expected a function or variable declaration",
+                ),
+            }
+        }
+
+        /* Handles:
+         *   Type name = expr;
+         */
+        fn parse_var_decl(&mut self, ttype: Type, name: String, start: Span) -> ASTNode {
+            let init = self.parse_expr();
+            let span = start.to(init.span());
+
+            ASTNode::VarDecl {
+                ttype,
+                name,
+                init: Box::new(init),
+                span,
+            }
+        }
+
+        fn parse_decl_func(&mut self, ret_type: Type, name: String, start: Span) -> ASTNode {
+            let params = self.parse_decl_func_params();
+            let (body, body_span) = self.parse_block();
+
+            ASTNode::FuncDecl {
+                name,
+                ret_type,
+                params,
+                body,
+                span: start.to(body_span),
+            }
+        }
+
+        /* Handles:
+         *   ()
+         *   (void)
+         *   (Type name, Type name, ...)
+         */
+        fn parse_decl_func_params(&mut self) -> Vec<FuncParam> {
+            if self.at().kind == TK::ClosPar {
+                self.eat(); // remove `)`
+                return vec![];
+            }
+
+            let first = self.eat();
+            if first.value == "void" {
+                self.eat_kind(TK::ClosPar);
+                return vec![];
+            }
+
+            let mut params = vec![self.parse_decl_func_param(first)];
+            while self.at().kind == TK::Comma {
+                self.eat(); // remove `,`
+                let ttype_tok = self.eat_kind(TK::StrLit);
+                params.push(self.parse_decl_func_param(ttype_tok));
+            }
+
+            self.eat_kind(TK::ClosPar);
+            params
+        }
+
+        fn parse_decl_func_param(&mut self, ttype_tok: Token) -> FuncParam {
+            let name = self.eat_kind(TK::StrLit);
+            FuncParam {
+                ttype: self.str2type(&ttype_tok.value, ttype_tok.span),
+                name: name.value,
+            }
+        }
+
+        /* Handles:
+         *   #include ...
+         *   #define ...
+         */
+        fn parse_deretive(&mut self, start: Span) -> ASTNode {
+            let at = self.eat_kind(TK::StrLit);
+            match at.value.as_str() {
+                "include" => self.parse_deretive_include(start),
+                "define" => self.parse_deretive_define(),
+                _ => diag::report(
+                    self.src,
+                    at.span,
+                    &format!("unknown preprocessing directive `{}`", at.value),
+                ),
+            }
+        }
+
+        /* Handles:
+         *   #include ...
+         */
+        fn parse_deretive_include(&mut self, start: Span) -> ASTNode {
+            let at = self.eat();
+
+            let mut filepath = String::new();
+            let mut end = at.span;
+
+            match at.kind {
+                TK::LessThan => {
+                    while self.at().kind != TK::GraThan {
+                        let x = self.eat();
+                        match x.kind {
+                            TK::StrLit | TK::DivSymb | TK::Dot => {
+                                filepath.extend(x.value.chars());
+                            }
+                            _ => diag::report(self.src, x.span, "invalid character in file path"),
+                        }
+                    }
+                    end = self.eat_kind(TK::GraThan).span;
+                }
+                TK::StrVal => {
+                    filepath.extend(at.value.chars());
+                }
+                _ => diag::report(
+                    self.src,
+                    at.span,
+                    &format!("unknown preprocessing directive `{}`", at.value),
+                ),
+            }
+
+            ASTNode::Include(filepath, start.to(end))
+        }
+
+        fn parse_deretive_define(&mut self) -> ASTNode {
+            todo!();
+        }
+
+        fn parse_prim_expr(&mut self) -> ASTNode {
+            let at = self.eat();
+
+            match at.kind {
+                TK::StrLit => ASTNode::StrLit(at.value, at.span),
+                TK::StrVal => ASTNode::StrVal(at.value, at.span),
+                TK::Numeric => ASTNode::IntLit(at.value.parse::<i32>().unwrap(), at.span),
+                TK::Semicolon => ASTNode::Semicolon(at.span),
+                _ => diag::report(
+                    self.src,
+                    at.span,
+                    &format!("unsupported primary expression {}", at.kind.describe()),
+                ),
+            }
+        }
+
+        /*
+         * HELPER Functions 👇
+         *
+         */
+
+        fn eat_kind(&mut self, kind: TK) -> Token {
+            let at = self.at();
+            if at.kind != kind {
+                diag::report(
+                    self.src,
+                    at.span,
+                    &format!("expected {} but found {}", kind.describe(), at.kind.describe()),
+                );
+            }
+            self.eat()
+        }
+
+        fn at(&mut self) -> Token {
+            if self.eof() {
+                diag::report(self.src, self.eof_span(), "unexpected end of file");
+            }
+            self.tokens.first().unwrap().clone()
+        }
+
+        fn eat(&mut self) -> Token {
+            if self.eof() {
+                diag::report(self.src, self.eof_span(), "unexpected end of file");
+            }
+            self.tokens.remove(0)
+        }
+
+        /// A zero-width span just past the last character, for diagnostics
+        /// that have run out of tokens to point at.
+        fn eof_span(&self) -> Span {
+            Span::new(self.src.len(), self.src.len())
+        }
+
+        fn eof(&self) -> bool {
+            self.tokens.is_empty()
+        }
+
+        fn is_decl(&self, x: &str) -> bool {
+            x == "int"
+        }
+
+        fn str2type(&self, s: &str, span: Span) -> Type {
+            match s {
+                "int" => Type::INT,
+                _ => diag::report(self.src, span, &format!("unknown type name `{s}`")),
+            }
+        }
+
+        fn get_strlit_val(&self, strlit: &ASTNode) -> String {
+            if let ASTNode::StrLit(value, _) = strlit {
+                return value.to_string();
+            }
+            diag::report(
+                self.src,
+                strlit.span(),
+                &format!("expected an identifier but found {:#?}", strlit),
+            );
+        }
+    }
+
+    /// Binding powers for binary operators: `(left, right)`. A higher left
+    /// binding power means tighter binding, so `*`/`/` win over `+`/`-`; the
+    /// right power being one greater than the left makes each operator
+    /// left-associative. Not a binary operator -> `None`.
+    fn binding_power(kind: &TK) -> Option<(u8, u8)> {
+        match kind {
+            TK::PluSymb | TK::MinSymb => Some((1, 2)),
+            TK::MulSymb | TK::DivSymb => Some((3, 4)),
+            _ => None,
+        }
+    }
+}
+
+pub mod codegen {
+    use ast::{ASTNode, AST};
+    use diag;
+    use exit;
+    use std::fs;
+    use types::Span;
+
+    /// Lowers an `AST` into x86_64 Linux NASM source. `printf` calls go out
+    /// through a raw `write` syscall, `main`'s `return` through `exit`, and
+    /// every other function's `return` through a real prologue/epilogue/
+    /// `ret`, so the emitted `.asm` needs nothing but NASM + `ld` to link.
+    pub struct Nasm<'a> {
+        src: &'a [char],
+        data: Vec<String>,
+        text: Vec<String>,
+        str_count: usize,
+        /// Whether the function currently being emitted is `main`: its
+        /// `return` lowers to `exit`, while every other function's `return`
+        /// lowers to a real `ret` through its own epilogue.
+        in_main: bool,
+        /// Whether the current function's body already emitted a `return`,
+        /// so `emit_func_decl` doesn't also append a redundant epilogue.
+        returned: bool,
+    }
+
+    impl<'a> Nasm<'a> {
+        pub fn new(src: &'a [char]) -> Self {
+            Self {
+                src,
+                data: vec![],
+                text: vec![],
+                str_count: 0,
+                in_main: false,
+                returned: false,
+            }
+        }
+
+        pub fn emit(&mut self, mut ast: AST, out_path: &str) {
+            self.text.push("global _start".to_string());
+
+            loop {
+                let node = match ast.next() {
+                    Some(ASTNode::EOF) | None => break,
+                    Some(node) => node,
+                };
+
+                self.emit_node(node);
+            }
+
+            if let Err(err) = fs::write(out_path, self.render()) {
+                eprintln!("ERROR:{}: Couldn't write {out_path}: {err:?}", line!());
+                exit(1);
+            }
+        }
+
+        fn emit_node(&mut self, node: ASTNode) {
+            match node {
+                ASTNode::Include(_, _) => {}
+                ASTNode::FuncDecl { name, body, .. } => self.emit_func_decl(name, body),
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("codegen: {:#?} not supported yet", other),
+                ),
+            }
+        }
+
+        fn emit_func_decl(&mut self, name: String, body: Vec<Box<ASTNode>>) {
+            // `main` is the process entry point; every other function keeps
+            // its own name as a label and is a real callable with a
+            // prologue/epilogue around it.
+            let is_main = name == "main";
+            let label = if is_main { "_start".to_string() } else { name };
+
+            self.text.push(format!("{label}:"));
+            if !is_main {
+                self.text.push("    push rbp".to_string());
+                self.text.push("    mov rbp, rsp".to_string());
+            }
+
+            self.in_main = is_main;
+            self.returned = false;
+            for stmt in body {
+                self.emit_stmt(*stmt);
+            }
+
+            if !is_main && !self.returned {
+                self.text.push("    pop rbp".to_string());
+                self.text.push("    ret".to_string());
+            }
+        }
+
+        fn emit_stmt(&mut self, node: ASTNode) {
+            match node {
+                ASTNode::FunCall { name, args, span } => self.emit_func_call(name, args, span),
+                ASTNode::Return(expr, span) => self.emit_return(*expr, span),
+                ASTNode::Semicolon(_) => {}
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("codegen: {:#?} not supported yet", other),
+                ),
+            }
+        }
+
+        fn emit_func_call(&mut self, name: String, args: Vec<Box<ASTNode>>, span: Span) {
+            if name != "printf" {
+                diag::report(
+                    self.src,
+                    span,
+                    &format!("codegen: only calls to `printf` are supported, found `{name}`"),
+                );
+            }
+
+            let arg = args
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| diag::report(self.src, span, "codegen: printf needs an argument"));
+
+            let text = match *arg {
+                ASTNode::StrVal(text, _) | ASTNode::StrLit(text, _) => text,
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    "codegen: printf only supports a literal string argument",
+                ),
+            };
+            let label = self.emit_string_literal(&text);
+
+            self.text.push("    mov rax, 1".to_string()); // syscall: write
+            self.text.push("    mov rdi, 1".to_string()); // fd: stdout
+            self.text.push(format!("    mov rsi, {label}"));
+            self.text.push(format!("    mov rdx, {label}_len"));
+            self.text.push("    syscall".to_string());
+        }
+
+        fn emit_return(&mut self, expr: ASTNode, _span: Span) {
+            let code = match expr {
+                ASTNode::IntLit(value, _) => value,
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    "codegen: only a literal integer return value is supported",
+                ),
+            };
+
+            if self.in_main {
+                self.text.push("    mov rax, 60".to_string()); // syscall: exit
+                self.text.push(format!("    mov rdi, {code}"));
+                self.text.push("    syscall".to_string());
+            } else {
+                self.text.push(format!("    mov rax, {code}"));
+                self.text.push("    pop rbp".to_string());
+                self.text.push("    ret".to_string());
+            }
+
+            self.returned = true;
+        }
+
+        /// Adds `text` to `section .data` under a fresh label and returns
+        /// that label, so callers can reference `label`/`label_len`.
+        ///
+        /// C's `printf` adds no trailing newline of its own, so `text`'s
+        /// bytes are emitted exactly as lexed (no newline is appended here —
+        /// one only shows up if `text` already contains one). Bytes are
+        /// emitted as comma-separated values rather than through `{:?}`:
+        /// NASM `db "..."` does not interpret backslash escapes the way
+        /// Rust's `Debug` does, so a source string containing a literal `\`
+        /// (the lexer does no escape processing) or non-ASCII bytes would
+        /// otherwise round-trip into invalid or wrong assembly.
+        fn emit_string_literal(&mut self, text: &str) -> String {
+            let label = format!("str{}", self.str_count);
+            self.str_count += 1;
+
+            let bytes: Vec<String> = text.bytes().map(|b| b.to_string()).collect();
+            if bytes.is_empty() {
+                self.data.push(format!("{label}: db 0"));
+                self.data.push(format!("{label}_len: equ 0"));
+            } else {
+                self.data.push(format!("{label}: db {}", bytes.join(", ")));
+                self.data.push(format!("{label}_len: equ $ - {label}"));
+            }
+
+            label
+        }
+
+        fn render(&self) -> String {
+            let mut out = String::new();
+
+            out.push_str("section .data\n");
+            for line in &self.data {
+                out.push_str("    ");
+                out.push_str(line);
+                out.push('\n');
+            }
+
+            out.push_str("\nsection .text\n");
+            for line in &self.text {
+                out.push_str(line);
+                out.push('\n');
+            }
+
+            out
+        }
+    }
+}
+
+pub mod compiler {
+    use ast::{ASTNode, AST};
+    use diag;
+    use std::collections::HashMap;
+    use types::{FuncParam, Span, TokenKind};
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum OpCode {
+        PushInt(i32),
+        /// Index into `Chunk::consts`.
+        PushStr(usize),
+        Add,
+        Sub,
+        Mul,
+        Div,
+        /// Discards the top of the operand stack, used to drop the value a
+        /// call leaves behind when it's used as a bare statement.
+        Pop,
+        /// Binds the top of the operand stack to a name in the current
+        /// call frame.
+        StoreLocal(String),
+        /// Pushes the current call frame's value for a name.
+        LoadLocal(String),
+        /// Unconditional jump to an absolute `Chunk::code` offset.
+        Jump(usize),
+        /// Pops the top of the stack; jumps to the offset if it's zero.
+        JumpIfFalse(usize),
+        /// Calls a user-defined function by name, pushing a fresh frame and
+        /// a return address.
+        Call(String),
+        /// Index into the fixed builtin table (`0` is `printf`).
+        CallBuiltin(usize),
+        Return,
+    }
+
+    /// Maps a function name to the offset of its first instruction in
+    /// `Chunk::code`, so the `vm` knows where to start running `main`.
+    #[derive(Clone, Debug, Default)]
+    pub struct Defs {
+        offsets: HashMap<String, usize>,
+    }
+
+    impl Defs {
+        pub fn insert(&mut self, name: String, offset: usize) {
+            self.offsets.insert(name, offset);
+        }
+
+        pub fn get(&self, name: &str) -> Option<usize> {
+            self.offsets.get(name).copied()
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Chunk {
+        pub code: Vec<OpCode>,
+        pub consts: Vec<String>,
+        pub defs: Defs,
+    }
+
+    /// Lowers an `AST` into a flat `Chunk` the `vm` can run. This is a
+    /// compile step separate from execution: it does no I/O and makes one
+    /// pass over the AST, whereas `runtime::Interpreter` runs the program as
+    /// it walks the tree.
+    pub struct Compiler<'a> {
+        src: &'a [char],
+        chunk: Chunk,
+    }
+
+    impl<'a> Compiler<'a> {
+        pub fn new(src: &'a [char]) -> Self {
+            Self {
+                src,
+                chunk: Chunk::default(),
+            }
+        }
+
+        pub fn compile(&mut self, mut ast: AST) -> Chunk {
+            loop {
+                let node = match ast.next() {
+                    Some(ASTNode::EOF) | None => break,
+                    Some(node) => node,
+                };
+
+                self.compile_node(node);
+            }
+
+            std::mem::take(&mut self.chunk)
+        }
+
+        fn compile_node(&mut self, node: ASTNode) {
+            match node {
+                ASTNode::Include(_, _) => {}
+                ASTNode::FuncDecl { name, params, body, .. } => self.compile_func_decl(name, params, body),
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("compiler: {:#?} not supported yet", other),
+                ),
+            }
+        }
+
+        fn compile_func_decl(&mut self, name: String, params: Vec<FuncParam>, body: Vec<Box<ASTNode>>) {
+            let offset = self.chunk.code.len();
+            self.chunk.defs.insert(name, offset);
+
+            // Args are pushed left-to-right by the caller, so the stack
+            // holds the last parameter on top; bind in reverse to match.
+            for param in params.iter().rev() {
+                self.chunk.code.push(OpCode::StoreLocal(param.name.clone()));
+            }
+
+            for stmt in body {
+                self.compile_stmt(*stmt);
+            }
+
+            // A body that falls off the end without an explicit `return`
+            // would otherwise run straight into whatever function was
+            // compiled next; every function must end on a `Return`. It is
+            // appended even after a trailing `return`, since an `if`'s jump
+            // can target the offset just past the body's last instruction.
+            self.chunk.code.push(OpCode::PushInt(0));
+            self.chunk.code.push(OpCode::Return);
+        }
+
+        fn compile_stmt(&mut self, node: ASTNode) {
+            match node {
+                ASTNode::FunCall { name, args, span } => {
+                    self.compile_func_call(name, args, span);
+                    // Discard the call's value: used as a statement, not an
+                    // expression, so nothing will consume it otherwise.
+                    self.chunk.code.push(OpCode::Pop);
+                }
+                ASTNode::Return(expr, _span) => {
+                    self.compile_expr(*expr);
+                    self.chunk.code.push(OpCode::Return);
+                }
+                ASTNode::VarDecl { name, init, .. } => {
+                    self.compile_expr(*init);
+                    self.chunk.code.push(OpCode::StoreLocal(name));
+                }
+                ASTNode::If { cond, then, else_, .. } => self.compile_if(*cond, then, else_),
+                ASTNode::While { cond, body, .. } => self.compile_while(*cond, body),
+                ASTNode::Semicolon(_) => {}
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("compiler: {:#?} not supported yet", other),
+                ),
+            }
+        }
+
+        fn compile_if(&mut self, cond: ASTNode, then: Vec<Box<ASTNode>>, else_: Option<Vec<Box<ASTNode>>>) {
+            self.compile_expr(cond);
+            let jump_if_false = self.emit_placeholder(OpCode::JumpIfFalse(usize::MAX));
+
+            for stmt in then {
+                self.compile_stmt(*stmt);
+            }
+
+            match else_ {
+                Some(else_body) => {
+                    let jump_over_else = self.emit_placeholder(OpCode::Jump(usize::MAX));
+                    self.patch_jump(jump_if_false);
+
+                    for stmt in else_body {
+                        self.compile_stmt(*stmt);
+                    }
+                    self.patch_jump(jump_over_else);
+                }
+                None => self.patch_jump(jump_if_false),
+            }
+        }
+
+        fn compile_while(&mut self, cond: ASTNode, body: Vec<Box<ASTNode>>) {
+            let loop_start = self.chunk.code.len();
+            self.compile_expr(cond);
+            let jump_if_false = self.emit_placeholder(OpCode::JumpIfFalse(usize::MAX));
+
+            for stmt in body {
+                self.compile_stmt(*stmt);
+            }
+            self.chunk.code.push(OpCode::Jump(loop_start));
+
+            self.patch_jump(jump_if_false);
+        }
+
+        /// Pushes a jump with a placeholder target and returns its offset,
+        /// to be filled in later by `patch_jump` once the real target is
+        /// known.
+        fn emit_placeholder(&mut self, jump: OpCode) -> usize {
+            self.chunk.code.push(jump);
+            self.chunk.code.len() - 1
+        }
+
+        /// Patches the jump at `at` to target the next instruction that
+        /// will be emitted.
+        fn patch_jump(&mut self, at: usize) {
+            let target = self.chunk.code.len();
+            match &mut self.chunk.code[at] {
+                OpCode::Jump(to) | OpCode::JumpIfFalse(to) => *to = target,
+                other => unreachable!("patch_jump target {:?} is not a jump", other),
+            }
+        }
+
+        fn compile_func_call(&mut self, name: String, args: Vec<Box<ASTNode>>, _span: Span) {
+            for arg in args {
+                self.compile_expr(*arg);
+            }
+
+            if name == "printf" {
+                self.chunk.code.push(OpCode::CallBuiltin(0));
+            } else {
+                self.chunk.code.push(OpCode::Call(name));
+            }
+        }
+
+        fn compile_expr(&mut self, node: ASTNode) {
+            match node {
+                ASTNode::IntLit(value, _) => self.chunk.code.push(OpCode::PushInt(value)),
+                ASTNode::StrVal(text, _) => {
+                    let idx = self.intern(text);
+                    self.chunk.code.push(OpCode::PushStr(idx));
+                }
+                ASTNode::StrLit(name, _) => self.chunk.code.push(OpCode::LoadLocal(name)),
+                ASTNode::FunCall { name, args, span } => self.compile_func_call(name, args, span),
+                ASTNode::BinaryOp { op, lhs, rhs, span } => {
+                    self.compile_expr(*lhs);
+                    self.compile_expr(*rhs);
+
+                    let opcode = match op {
+                        TokenKind::PluSymb => OpCode::Add,
+                        TokenKind::MinSymb => OpCode::Sub,
+                        TokenKind::MulSymb => OpCode::Mul,
+                        TokenKind::DivSymb => OpCode::Div,
+                        _ => diag::report(
+                            self.src,
+                            span,
+                            &format!("compiler: unsupported binary operator {:?}", op),
+                        ),
+                    };
+                    self.chunk.code.push(opcode);
+                }
+                other => diag::report(
+                    self.src,
+                    other.span(),
+                    &format!("compiler: {:#?} not supported yet", other),
+                ),
+            }
+        }
+
+        fn intern(&mut self, text: String) -> usize {
+            if let Some(idx) = self.chunk.consts.iter().position(|s| *s == text) {
+                return idx;
+            }
+
+            self.chunk.consts.push(text);
+            self.chunk.consts.len() - 1
+        }
+    }
+}
+
+pub mod vm {
+    use compiler::{Chunk, OpCode};
+    use std::collections::HashMap;
+    use std::process::exit;
+
+    /// Bounds on what a `Vm` run is allowed to do, so a malformed or
+    /// runaway program can't exhaust memory or loop forever.
+    pub struct VmLimits {
+        pub max_stack: usize,
+        pub fuel: usize,
+    }
+
+    impl VmLimits {
+        pub fn new(max_stack: usize, fuel: usize) -> Self {
+            Self { max_stack, fuel }
+        }
+    }
+
+    impl Default for VmLimits {
+        fn default() -> Self {
+            Self::new(256, 1_000_000)
+        }
+    }
+
+    /// Runs a `Chunk` against an operand stack, starting at `main`. Each
+    /// user-function call pushes a fresh variable frame (searched innermost
+    /// first, mirroring `runtime::Env`) and a return address, so `Return`
+    /// can tell a top-level halt from unwinding back into a caller.
+    pub struct Vm {
+        stack: Vec<i32>,
+        frames: Vec<HashMap<String, i32>>,
+        call_stack: Vec<usize>,
+        limits: VmLimits,
+    }
+
+    impl Vm {
+        pub fn new(limits: VmLimits) -> Self {
+            Self {
+                stack: vec![],
+                frames: vec![],
+                call_stack: vec![],
+                limits,
+            }
+        }
+
+        pub fn run(&mut self, chunk: &Chunk) {
+            let mut ip = chunk.defs.get("main").unwrap_or(0);
+            let mut fuel = self.limits.fuel;
+            self.frames.push(HashMap::new());
+
+            while ip < chunk.code.len() {
+                if fuel == 0 {
+                    eprintln!("ERROR: vm ran out of fuel ({} instructions)", self.limits.fuel);
+                    exit(1);
+                }
+                fuel -= 1;
+
+                match &chunk.code[ip] {
+                    OpCode::PushInt(value) => {
+                        self.push(*value);
+                        ip += 1;
+                    }
+                    OpCode::PushStr(idx) => {
+                        self.push(*idx as i32);
+                        ip += 1;
+                    }
+                    OpCode::Add => {
+                        let (a, b) = self.pop2();
+                        let value = self.checked(a.checked_add(b), "addition");
+                        self.push(value);
+                        ip += 1;
+                    }
+                    OpCode::Sub => {
+                        let (a, b) = self.pop2();
+                        let value = self.checked(a.checked_sub(b), "subtraction");
+                        self.push(value);
+                        ip += 1;
+                    }
+                    OpCode::Mul => {
+                        let (a, b) = self.pop2();
+                        let value = self.checked(a.checked_mul(b), "multiplication");
+                        self.push(value);
+                        ip += 1;
+                    }
+                    OpCode::Div => {
+                        let (a, b) = self.pop2();
+                        if b == 0 {
+                            eprintln!("ERROR: vm division by zero");
+                            exit(1);
+                        }
+                        let value = self.checked(a.checked_div(b), "division");
+                        self.push(value);
+                        ip += 1;
+                    }
+                    OpCode::Pop => {
+                        self.pop();
+                        ip += 1;
+                    }
+                    OpCode::StoreLocal(name) => {
+                        let value = self.pop();
+                        self.frames.last_mut().unwrap().insert(name.clone(), value);
+                        ip += 1;
+                    }
+                    OpCode::LoadLocal(name) => {
+                        let value = self
+                            .frames
+                            .iter()
+                            .rev()
+                            .find_map(|frame| frame.get(name).copied())
+                            .unwrap_or_else(|| {
+                                eprintln!("ERROR: vm: undefined variable `{name}`");
+                                exit(1);
+                            });
+                        self.push(value);
+                        ip += 1;
+                    }
+                    OpCode::Jump(target) => ip = *target,
+                    OpCode::JumpIfFalse(target) => {
+                        let value = self.pop();
+                        ip = if value == 0 { *target } else { ip + 1 };
+                    }
+                    OpCode::Call(name) => {
+                        let target = chunk.defs.get(name).unwrap_or_else(|| {
+                            eprintln!("ERROR: vm: call to undefined function `{name}`");
+                            exit(1);
+                        });
+                        self.call_stack.push(ip + 1);
+                        self.frames.push(HashMap::new());
+                        ip = target;
+                    }
+                    OpCode::CallBuiltin(0) => {
+                        let idx = self.pop();
+                        print!("{}", chunk.consts[idx as usize]);
+                        self.push(0);
+                        ip += 1;
+                    }
+                    OpCode::CallBuiltin(other) => {
+                        eprintln!("ERROR: vm: unknown builtin #{other}");
+                        exit(1);
+                    }
+                    OpCode::Return => {
+                        let value = self.pop();
+                        self.frames.pop();
+
+                        match self.call_stack.pop() {
+                            Some(return_to) => {
+                                self.push(value);
+                                ip = return_to;
+                            }
+                            None => {
+                                println!("{value}");
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        fn push(&mut self, value: i32) {
+            if self.stack.len() >= self.limits.max_stack {
+                eprintln!("ERROR: vm stack overflow (limit {})", self.limits.max_stack);
+                exit(1);
+            }
+            self.stack.push(value);
+        }
+
+        fn pop(&mut self) -> i32 {
+            self.stack.pop().unwrap_or_else(|| {
+                eprintln!("ERROR: vm stack underflow");
+                exit(1);
+            })
+        }
+
+        fn pop2(&mut self) -> (i32, i32) {
+            let b = self.pop();
+            let a = self.pop();
+            (a, b)
+        }
+
+        /// Aborts cleanly instead of panicking on a Rust integer overflow
+        /// when `op_name`'s checked arithmetic overflows.
+        fn checked(&self, result: Option<i32>, op_name: &str) -> i32 {
+            result.unwrap_or_else(|| {
+                eprintln!("ERROR: vm arithmetic overflow in {op_name}");
+                exit(1);
+            })
+        }
+    }
+}
+
+pub mod lexer {
+    use diag;
+    use types::{Span, Token, TokenKind};
+
+    pub struct Lexer<'a> {
+        origin: &'a [char],
+        stream: &'a [char],
+        tokens: Vec<Token>,
+    }
+
+    impl<'a> Lexer<'a> {
+        pub fn new(stream: &'a [char]) -> Self {
+            Self {
+                origin: stream,
+                stream,
+                tokens: Vec::new(),
+            }
+        }
+
+        pub fn lex(&mut self) -> Vec<Token> {
+            loop {
+                self.trim_left();
+
+                if self.stream.is_empty() {
+                    break;
+                }
+
+                // [a..z] + [0-9]
+                if self.stream[0].is_alphabetic() {
+                    let start = self.offset();
+                    let buf = self.chop_while(|c| c.is_alphabetic());
+                    self.push_token(Token {
+                        kind: TokenKind::StrLit,
+                        value: buf.iter().collect::<String>(),
+                        span: Span::new(start, self.offset()),
+                    });
+                    continue;
+                }
+
+                // [0-9]
+                if self.stream[0].is_numeric() {
+                    let start = self.offset();
+                    let buf = self.chop_while(|c| c.is_numeric());
+                    self.push_token(Token {
+                        kind: TokenKind::Numeric,
+                        value: buf.iter().collect::<String>(),
+                        span: Span::new(start, self.offset()),
+                    });
+                    continue;
+                }
+
+                if self.stream[0] == '"' {
+                    let start = self.offset();
+                    self.chop(1); // remove `"`
+                    let buf = self.chop_while(|c| *c != '"');
+                    self.chop(1); // remove `"`
+                    self.push_token(Token {
+                        kind: TokenKind::StrVal,
+                        value: buf.iter().collect::<String>(),
+                        span: Span::new(start, self.offset()),
+                    });
+                    continue;
+                }
+
+                if self.extr_sgl_char_tkn() {
+                    continue;
+                }
+
+                let start = self.offset();
+                let bad = self.chop(1).iter().collect::<String>();
+                diag::report(
+                    self.origin,
+                    Span::new(start, self.offset()),
+                    &format!("unexpected character '{bad}'"),
+                );
+            }
+
+            self.tokens.clone()
+        }
+
+        fn extr_sgl_char_tkn(&mut self) -> bool {
+            for x in SINGLE_CHAR_TOKENS {
+                if (x).value == self.stream[0] {
+                    let start = self.offset();
+                    let buf = self.chop(1).iter().collect::<String>();
+                    self.push_token(Token {
+                        kind: (x).kind,
+                        value: buf,
+                        span: Span::new(start, self.offset()),
+                    });
+                    return true;
+                }
+            }
+            false
+        }
+
+        /// Char offset into the original source that `self.stream` has
+        /// advanced to, used to stamp spans on the tokens we emit.
+        fn offset(&self) -> usize {
+            self.origin.len() - self.stream.len()
+        }
+
+        fn chop_while<P>(&mut self, mut predicate: P) -> &'a [char]
+        where
+            P: FnMut(&char) -> bool,
+        {
+            let mut n = 0;
+            while n < self.stream.len() && predicate(&self.stream[n]) {
+                n += 1;
+            }
+            self.chop(n)
+        }
+
+        fn chop(&mut self, n: usize) -> &'a [char] {
+            let buf = &self.stream[0..n];
+            self.stream = &self.stream[n..];
+            buf
+        }
+
+        fn trim_left(&mut self) -> usize {
+            let mut n = 0;
+            while !self.stream.is_empty() && self.stream[0].is_whitespace() {
+                self.stream = &self.stream[1..];
+                n += 1;
+            }
+            n
+        }
+
+        fn push_token(&mut self, lexeme: Token) {
+            self.tokens.push(lexeme);
+        }
+    }
+
+    struct SingleCharToken {
+        kind: TokenKind,
+        value: char,
+    }
+
+    const SINGLE_CHAR_TOKENS: [SingleCharToken; 16] = [
+        SingleCharToken {
+            kind: TokenKind::OpenPar,
+            value: '(',
+        },
+        SingleCharToken {
+            kind: TokenKind::ClosPar,
+            value: ')',
+        },
+        SingleCharToken {
+            kind: TokenKind::OpenBlk,
+            value: '{',
+        },
+        SingleCharToken {
+            kind: TokenKind::ClosBlk,
+            value: '}',
+        },
+        SingleCharToken {
+            kind: TokenKind::Colon,
+            value: ':',
+        },
+        SingleCharToken {
+            kind: TokenKind::Comma,
+            value: ',',
+        },
+        SingleCharToken {
+            kind: TokenKind::Semicolon,
+            value: ';',
+        },
+        SingleCharToken {
+            kind: TokenKind::Equal,
+            value: '=',
+        },
+        SingleCharToken {
+            kind: TokenKind::PluSymb,
+            value: '+',
+        },
+        SingleCharToken {
+            kind: TokenKind::MinSymb,
+            value: '-',
+        },
+        SingleCharToken {
+            kind: TokenKind::MulSymb,
+            value: '*',
+        },
+        SingleCharToken {
+            kind: TokenKind::DivSymb,
+            value: '/',
+        },
+        SingleCharToken {
+            kind: TokenKind::Dot,
+            value: '.',
+        },
+        SingleCharToken {
+            kind: TokenKind::Hash,
+            value: '#',
+        },
+        SingleCharToken {
+            kind: TokenKind::LessThan,
+            value: '<',
+        },
+        SingleCharToken {
+            kind: TokenKind::GraThan,
+            value: '>',
+        },
+    ];
+}
@@ -0,0 +1,267 @@
+//! Golden-file conformance tests: each accept fixture in `tests/fixtures` is
+//! fed through `lexer` + `parser` and checked against a tree hand-written
+//! here; a reject fixture is checked for its diagnostic instead.
+//! Spans carry real char offsets that shift whenever a fixture gains or
+//! loses a character, so comparisons go through `assert_eq_ignore_span!`
+//! rather than a plain `assert_eq!`.
+
+extern crate cir;
+
+use cir::ast::ASTNode;
+use cir::lexer::Lexer;
+use cir::parser::Parser;
+use cir::types::{Span, Type};
+
+fn dummy_span() -> Span {
+    Span::new(0, 0)
+}
+
+/// Structural equality for `ASTNode` that ignores every `Span` field, so a
+/// fixture can assert shape without caring about byte offsets.
+fn ast_eq_ignore_span(a: &ASTNode, b: &ASTNode) -> bool {
+    match (a, b) {
+        (ASTNode::Include(af, _), ASTNode::Include(bf, _)) => af == bf,
+        (
+            ASTNode::FuncDecl { name: an, params: ap, ret_type: art, body: ab, .. },
+            ASTNode::FuncDecl { name: bn, params: bp, ret_type: brt, body: bb, .. },
+        ) => {
+            an == bn
+                && ap == bp
+                && art == brt
+                && ab.len() == bb.len()
+                && ab.iter().zip(bb.iter()).all(|(x, y)| ast_eq_ignore_span(x, y))
+        }
+        (
+            ASTNode::FunCall { name: an, args: aa, .. },
+            ASTNode::FunCall { name: bn, args: ba, .. },
+        ) => {
+            an == bn
+                && aa.len() == ba.len()
+                && aa.iter().zip(ba.iter()).all(|(x, y)| ast_eq_ignore_span(x, y))
+        }
+        (
+            ASTNode::BinaryOp { op: ao, lhs: al, rhs: ar, .. },
+            ASTNode::BinaryOp { op: bo, lhs: bl, rhs: br, .. },
+        ) => ao == bo && ast_eq_ignore_span(al, bl) && ast_eq_ignore_span(ar, br),
+        (
+            ASTNode::VarDecl { ttype: at, name: an, init: ai, .. },
+            ASTNode::VarDecl { ttype: bt, name: bn, init: bi, .. },
+        ) => at == bt && an == bn && ast_eq_ignore_span(ai, bi),
+        (
+            ASTNode::If { cond: ac, then: at, else_: ae, .. },
+            ASTNode::If { cond: bc, then: bt, else_: be, .. },
+        ) => {
+            ast_eq_ignore_span(ac, bc)
+                && at.len() == bt.len()
+                && at.iter().zip(bt.iter()).all(|(x, y)| ast_eq_ignore_span(x, y))
+                && match (ae, be) {
+                    (Some(a), Some(b)) => {
+                        a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| ast_eq_ignore_span(x, y))
+                    }
+                    (None, None) => true,
+                    _ => false,
+                }
+        }
+        (
+            ASTNode::While { cond: ac, body: ab, .. },
+            ASTNode::While { cond: bc, body: bb, .. },
+        ) => {
+            ast_eq_ignore_span(ac, bc)
+                && ab.len() == bb.len()
+                && ab.iter().zip(bb.iter()).all(|(x, y)| ast_eq_ignore_span(x, y))
+        }
+        (ASTNode::Return(ae, _), ASTNode::Return(be, _)) => ast_eq_ignore_span(ae, be),
+        (ASTNode::StrLit(a, _), ASTNode::StrLit(b, _)) => a == b,
+        (ASTNode::StrVal(a, _), ASTNode::StrVal(b, _)) => a == b,
+        (ASTNode::IntLit(a, _), ASTNode::IntLit(b, _)) => a == b,
+        (ASTNode::Semicolon(_), ASTNode::Semicolon(_)) => true,
+        (ASTNode::EOF, ASTNode::EOF) => true,
+        _ => false,
+    }
+}
+
+/// Asserts two `ASTNode`s are equal while ignoring `Span`s, panicking with
+/// both sides pretty-printed (spans and all) on mismatch.
+macro_rules! assert_eq_ignore_span {
+    ($left:expr, $right:expr) => {
+        let left = &$left;
+        let right = &$right;
+        if !ast_eq_ignore_span(left, right) {
+            panic!(
+                "AST mismatch (spans ignored):\n  left:  {:#?}\n  right: {:#?}",
+                left, right
+            );
+        }
+    };
+}
+
+/// Lexes and parses `src`, draining the resulting `AST` into a `Vec` so it
+/// can be compared node-by-node.
+fn parse(src: &str) -> Vec<ASTNode> {
+    let chars: Vec<char> = src.chars().collect();
+
+    let mut lexer = Lexer::new(&chars);
+    let tokens = lexer.lex();
+
+    let mut parser = Parser::new(&chars, tokens);
+    let mut ast = parser.parse();
+
+    let mut nodes = vec![];
+    while let Some(node) = ast.next() {
+        nodes.push(node);
+    }
+    nodes
+}
+
+fn assert_nodes_eq(actual: &[ASTNode], expected: &[ASTNode]) {
+    assert_eq!(actual.len(), expected.len(), "node count mismatch");
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert_eq_ignore_span!(a, e);
+    }
+}
+
+#[test]
+fn accepts_arithmetic_with_precedence() {
+    let nodes = parse(include_str!("fixtures/arithmetic.c"));
+
+    let expected = vec![
+        ASTNode::Include("stdio.h".to_string(), dummy_span()),
+        ASTNode::FuncDecl {
+            name: "main".to_string(),
+            params: vec![],
+            ret_type: Type::INT,
+            body: vec![
+                Box::new(ASTNode::Return(
+                    Box::new(ASTNode::BinaryOp {
+                        op: cir::types::TokenKind::PluSymb,
+                        lhs: Box::new(ASTNode::IntLit(1, dummy_span())),
+                        rhs: Box::new(ASTNode::BinaryOp {
+                            op: cir::types::TokenKind::MulSymb,
+                            lhs: Box::new(ASTNode::IntLit(2, dummy_span())),
+                            rhs: Box::new(ASTNode::IntLit(3, dummy_span())),
+                            span: dummy_span(),
+                        }),
+                        span: dummy_span(),
+                    }),
+                    dummy_span(),
+                )),
+                Box::new(ASTNode::Semicolon(dummy_span())),
+            ],
+            span: dummy_span(),
+        },
+        ASTNode::EOF,
+    ];
+
+    assert_nodes_eq(&nodes, &expected);
+}
+
+#[test]
+fn accepts_params_vardecl_if_while() {
+    let nodes = parse(include_str!("fixtures/if_while.c"));
+
+    let add = ASTNode::FuncDecl {
+        name: "add".to_string(),
+        params: vec![
+            cir::types::FuncParam { ttype: Type::INT, name: "a".to_string() },
+            cir::types::FuncParam { ttype: Type::INT, name: "b".to_string() },
+        ],
+        ret_type: Type::INT,
+        body: vec![
+            Box::new(ASTNode::Return(
+                Box::new(ASTNode::BinaryOp {
+                    op: cir::types::TokenKind::PluSymb,
+                    lhs: Box::new(ASTNode::StrLit("a".to_string(), dummy_span())),
+                    rhs: Box::new(ASTNode::StrLit("b".to_string(), dummy_span())),
+                    span: dummy_span(),
+                }),
+                dummy_span(),
+            )),
+            Box::new(ASTNode::Semicolon(dummy_span())),
+        ],
+        span: dummy_span(),
+    };
+
+    let main = ASTNode::FuncDecl {
+        name: "main".to_string(),
+        params: vec![],
+        ret_type: Type::INT,
+        body: vec![
+            Box::new(ASTNode::VarDecl {
+                ttype: Type::INT,
+                name: "x".to_string(),
+                init: Box::new(ASTNode::IntLit(5, dummy_span())),
+                span: dummy_span(),
+            }),
+            Box::new(ASTNode::Semicolon(dummy_span())),
+            Box::new(ASTNode::If {
+                cond: Box::new(ASTNode::StrLit("x".to_string(), dummy_span())),
+                then: vec![Box::new(ASTNode::FunCall {
+                    name: "printf".to_string(),
+                    args: vec![Box::new(ASTNode::StrVal("nonzero".to_string(), dummy_span()))],
+                    span: dummy_span(),
+                })],
+                else_: Some(vec![Box::new(ASTNode::FunCall {
+                    name: "printf".to_string(),
+                    args: vec![Box::new(ASTNode::StrVal("zero".to_string(), dummy_span()))],
+                    span: dummy_span(),
+                })]),
+                span: dummy_span(),
+            }),
+            Box::new(ASTNode::While {
+                cond: Box::new(ASTNode::StrLit("x".to_string(), dummy_span())),
+                body: vec![Box::new(ASTNode::FunCall {
+                    name: "add".to_string(),
+                    args: vec![
+                        Box::new(ASTNode::StrLit("x".to_string(), dummy_span())),
+                        Box::new(ASTNode::IntLit(1, dummy_span())),
+                    ],
+                    span: dummy_span(),
+                })],
+                span: dummy_span(),
+            }),
+            Box::new(ASTNode::Return(
+                Box::new(ASTNode::FunCall {
+                    name: "add".to_string(),
+                    args: vec![
+                        Box::new(ASTNode::StrLit("x".to_string(), dummy_span())),
+                        Box::new(ASTNode::IntLit(10, dummy_span())),
+                    ],
+                    span: dummy_span(),
+                }),
+                dummy_span(),
+            )),
+            Box::new(ASTNode::Semicolon(dummy_span())),
+        ],
+        span: dummy_span(),
+    };
+
+    assert_nodes_eq(&nodes, &[add, main, ASTNode::EOF]);
+}
+
+/// Reject case: the parser has no error-recovery, so a malformed fixture is
+/// driven through the real binary in its own working directory (it hardcodes
+/// reading `main.c`) and checked for the diagnostic `diag::report` prints on
+/// stderr plus a non-zero exit, rather than calling `Parser` in-process where
+/// the same failure would abort the whole test run.
+#[test]
+fn rejects_missing_closing_paren() {
+    let dir = std::env::temp_dir().join(format!("cir-golden-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy("tests/fixtures/missing_paren.c", dir.join("main.c")).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cir"))
+        .current_dir(&dir)
+        .output()
+        .expect("failed to run cir binary");
+
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(!output.status.success(), "expected a parse failure");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("main.c:"), "stderr: {}", stderr);
+    assert!(
+        stderr.contains("expected ')' but found '{'"),
+        "stderr: {}",
+        stderr
+    );
+}